@@ -1,6 +1,7 @@
+use chrono::{DateTime, FixedOffset, TimeZone};
 use serde::{Deserialize, Serialize};
-use std::collections::{BTreeMap, HashMap};
-use std::fs::File;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs::{File, OpenOptions};
 use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
 
@@ -20,6 +21,56 @@ pub enum DataType {
     Int,
     Float,
     Str,
+    Bool,
+    Datetime,
+}
+
+/// A parsed RFC 3339 timestamp: seconds since the Unix epoch plus the
+/// timezone offset (in seconds) it was originally expressed in. Equality
+/// and ordering only consider `epoch`, since that's what identifies the
+/// instant; `offset` is kept around to round-trip the original string.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+pub struct DateTimeValue {
+    pub epoch: i64,
+    pub offset: i32,
+}
+
+impl DateTimeValue {
+    pub fn to_rfc3339(&self) -> String {
+        let tz = FixedOffset::east_opt(self.offset).unwrap_or_else(|| FixedOffset::east_opt(0).unwrap());
+        tz.timestamp_opt(self.epoch, 0)
+            .single()
+            .map(|dt| dt.to_rfc3339())
+            .unwrap_or_default()
+    }
+}
+
+impl PartialEq for DateTimeValue {
+    fn eq(&self, other: &Self) -> bool {
+        self.epoch == other.epoch
+    }
+}
+
+impl Eq for DateTimeValue {}
+
+impl PartialOrd for DateTimeValue {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for DateTimeValue {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.epoch.cmp(&other.epoch)
+    }
+}
+
+fn parse_datetime(val: &str) -> Result<DateTimeValue, Error> {
+    let parsed = DateTime::parse_from_rfc3339(val).map_err(|_| Error::InvalidIndex)?;
+    Ok(DateTimeValue {
+        epoch: parsed.timestamp(),
+        offset: parsed.offset().local_minus_utc(),
+    })
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -27,6 +78,8 @@ pub enum ResultDT {
     Int(i32),
     Float(f64),
     Str(String),
+    Bool(bool),
+    Datetime(DateTimeValue),
     None,
 }
 
@@ -45,6 +98,14 @@ impl PartialEq for ResultDT {
                 ResultDT::Str(val2) => val == val2,
                 _ => false,
             },
+            ResultDT::Bool(val) => match other {
+                ResultDT::Bool(val2) => val == val2,
+                _ => false,
+            },
+            ResultDT::Datetime(val) => match other {
+                ResultDT::Datetime(val2) => val == val2,
+                _ => false,
+            },
             ResultDT::None => match other {
                 ResultDT::None => true,
                 _ => false,
@@ -55,15 +116,178 @@ impl PartialEq for ResultDT {
 
 impl Eq for ResultDT {}
 
+impl PartialOrd for ResultDT {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        match (self, other) {
+            (ResultDT::Int(val), ResultDT::Int(val2)) => val.partial_cmp(val2),
+            (ResultDT::Float(val), ResultDT::Float(val2)) => val.partial_cmp(val2),
+            (ResultDT::Str(val), ResultDT::Str(val2)) => val.partial_cmp(val2),
+            (ResultDT::Bool(val), ResultDT::Bool(val2)) => val.partial_cmp(val2),
+            (ResultDT::Datetime(val), ResultDT::Datetime(val2)) => val.partial_cmp(val2),
+            _ => None,
+        }
+    }
+}
+
+/// A single WHERE-clause condition over a named column, evaluated by
+/// `Database::select`.
+pub enum Predicate {
+    Eq(String, String),
+    Lt(String, String),
+    Le(String, String),
+    Gt(String, String),
+    Ge(String, String),
+    Between(String, String, String),
+}
+
+impl Predicate {
+    fn col(&self) -> &str {
+        match self {
+            Predicate::Eq(col, _)
+            | Predicate::Lt(col, _)
+            | Predicate::Le(col, _)
+            | Predicate::Gt(col, _)
+            | Predicate::Ge(col, _)
+            | Predicate::Between(col, _, _) => col,
+        }
+    }
+}
+
+fn parse_value(kind: &DataType, val: &str) -> Result<ResultDT, Error> {
+    match kind {
+        DataType::Int => val
+            .parse::<i32>()
+            .map(ResultDT::Int)
+            .map_err(|_| Error::InvalidIndex),
+        DataType::Float => val
+            .parse::<f64>()
+            .map(ResultDT::Float)
+            .map_err(|_| Error::InvalidIndex),
+        DataType::Str => Ok(ResultDT::Str(val.to_string())),
+        DataType::Bool => val
+            .parse::<bool>()
+            .map(ResultDT::Bool)
+            .map_err(|_| Error::InvalidIndex),
+        DataType::Datetime => parse_datetime(val).map(ResultDT::Datetime),
+    }
+}
+
+fn passes(predicate: &Predicate, kind: &DataType, val: &ResultDT) -> Result<bool, Error> {
+    Ok(match predicate {
+        Predicate::Eq(_, v) => val == &parse_value(kind, v)?,
+        Predicate::Lt(_, v) => val < &parse_value(kind, v)?,
+        Predicate::Le(_, v) => val <= &parse_value(kind, v)?,
+        Predicate::Gt(_, v) => val > &parse_value(kind, v)?,
+        Predicate::Ge(_, v) => val >= &parse_value(kind, v)?,
+        Predicate::Between(_, lo, hi) => {
+            val >= &parse_value(kind, lo)? && val <= &parse_value(kind, hi)?
+        }
+    })
+}
+
+/// Collects row positions in `index` whose key satisfies `predicate`,
+/// using `BTreeMap::range` so indexed lookups stay O(log n + k). Each key
+/// maps to every row holding that value, so duplicate-valued columns
+/// return all of their matches rather than just one.
+fn int_range_positions(
+    index: &BTreeMap<i32, Vec<usize>>,
+    predicate: &Predicate,
+) -> Result<Vec<usize>, Error> {
+    use std::ops::Bound::{Excluded, Included, Unbounded};
+
+    let parse = |v: &str| v.parse::<i32>().map_err(|_| Error::InvalidIndex);
+    let bounds = match predicate {
+        Predicate::Eq(_, v) => (Included(parse(v)?), Included(parse(v)?)),
+        Predicate::Lt(_, v) => (Unbounded, Excluded(parse(v)?)),
+        Predicate::Le(_, v) => (Unbounded, Included(parse(v)?)),
+        Predicate::Gt(_, v) => (Excluded(parse(v)?), Unbounded),
+        Predicate::Ge(_, v) => (Included(parse(v)?), Unbounded),
+        Predicate::Between(_, lo, hi) => (Included(parse(lo)?), Included(parse(hi)?)),
+    };
+
+    Ok(index
+        .range(bounds)
+        .flat_map(|(_, positions)| positions.iter().copied())
+        .collect())
+}
+
+fn str_range_positions(index: &BTreeMap<String, Vec<usize>>, predicate: &Predicate) -> Vec<usize> {
+    use std::ops::Bound::{Excluded, Included, Unbounded};
+
+    let bounds = match predicate {
+        Predicate::Eq(_, v) => (Included(v.clone()), Included(v.clone())),
+        Predicate::Lt(_, v) => (Unbounded, Excluded(v.clone())),
+        Predicate::Le(_, v) => (Unbounded, Included(v.clone())),
+        Predicate::Gt(_, v) => (Excluded(v.clone()), Unbounded),
+        Predicate::Ge(_, v) => (Included(v.clone()), Unbounded),
+        Predicate::Between(_, lo, hi) => (Included(lo.clone()), Included(hi.clone())),
+    };
+
+    index
+        .range(bounds)
+        .flat_map(|(_, positions)| positions.iter().copied())
+        .collect()
+}
+
+fn datetime_range_positions(
+    index: &BTreeMap<i64, Vec<usize>>,
+    predicate: &Predicate,
+) -> Result<Vec<usize>, Error> {
+    use std::ops::Bound::{Excluded, Included, Unbounded};
+
+    let parse = |v: &str| parse_datetime(v).map(|dt| dt.epoch);
+    let bounds = match predicate {
+        Predicate::Eq(_, v) => (Included(parse(v)?), Included(parse(v)?)),
+        Predicate::Lt(_, v) => (Unbounded, Excluded(parse(v)?)),
+        Predicate::Le(_, v) => (Unbounded, Included(parse(v)?)),
+        Predicate::Gt(_, v) => (Excluded(parse(v)?), Unbounded),
+        Predicate::Ge(_, v) => (Included(parse(v)?), Unbounded),
+        Predicate::Between(_, lo, hi) => (Included(parse(lo)?), Included(parse(hi)?)),
+    };
+
+    Ok(index
+        .range(bounds)
+        .flat_map(|(_, positions)| positions.iter().copied())
+        .collect())
+}
+
+/// Writes `val` at `pos`, overwriting a reused (tombstoned) slot in place
+/// or appending when `pos` is past the end of `vec`.
+fn set_or_push<T>(vec: &mut Vec<T>, pos: usize, val: T) {
+    if pos < vec.len() {
+        vec[pos] = val;
+    } else {
+        vec.push(val);
+    }
+}
+
+/// Linear fallback for un-indexed (or full-text-indexed) columns.
+fn scan_positions(table: &Table, col: &Column, predicate: &Predicate) -> Result<Vec<usize>, Error> {
+    let data = table.rows.get(&col.name).ok_or(Error::InvalidColumn)?;
+
+    let mut positions = Vec::new();
+    for idx in 0..data.size() {
+        if passes(predicate, &col.kind, &data.get_from_idx(idx))? {
+            positions.push(idx);
+        }
+    }
+
+    Ok(positions)
+}
+
 #[derive(Deserialize, Serialize)]
 pub enum Index {
-    Int(BTreeMap<i32, usize>),
-    Str(BTreeMap<String, usize>),
+    Int(BTreeMap<i32, Vec<usize>>),
+    Str(BTreeMap<String, Vec<usize>>),
+    Text(BTreeMap<String, Vec<usize>>),
+    Datetime(BTreeMap<i64, Vec<usize>>),
     None,
 }
 
 impl Index {
-    pub fn get<S: Into<String>>(&self, val: S) -> Result<Option<&usize>, Error> {
+    /// Every variant maps a key to the positions of *all* rows holding it,
+    /// so a duplicate-valued column never loses a match to this lookup.
+    pub fn get<S: Into<String>>(&self, val: S) -> Result<Option<&Vec<usize>>, Error> {
         let val = val.into();
 
         match self {
@@ -72,11 +296,109 @@ impl Index {
                 _ => Err(Error::InvalidIndex),
             },
             Index::Str(index) => Ok(index.get(&val)),
+            Index::Text(_) => Err(Error::InvalidIndex),
+            Index::Datetime(index) => Ok(index.get(&parse_datetime(&val)?.epoch)),
             Index::None => Ok(None),
         }
     }
 }
 
+/// Lowercases, strips punctuation and splits `text` on word boundaries.
+fn tokenize(text: &str) -> Vec<String> {
+    text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|tok| !tok.is_empty())
+        .map(|tok| tok.to_string())
+        .collect()
+}
+
+/// Classic Levenshtein edit distance, used to fuzzy-match query tokens
+/// against the dictionary of indexed terms.
+fn levenshtein(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+
+    for i in 1..=a.len() {
+        let mut prev = row[0];
+        row[0] = i;
+        for j in 1..=b.len() {
+            let cur = row[j];
+            row[j] = if a[i - 1] == b[j - 1] {
+                prev
+            } else {
+                1 + prev.min(row[j]).min(row[j - 1])
+            };
+            prev = cur;
+        }
+    }
+
+    row[b.len()]
+}
+
+/// Enumerates the dictionary terms within `max_dist` edits of `token`,
+/// skipping candidates whose length alone rules them out.
+fn fuzzy_terms<'a>(
+    terms: &'a BTreeMap<String, Vec<usize>>,
+    token: &str,
+    max_dist: usize,
+) -> Vec<&'a String> {
+    terms
+        .keys()
+        .filter(|term| {
+            let len_diff = term.len().abs_diff(token.len());
+            len_diff <= max_dist && levenshtein(term, token) <= max_dist
+        })
+        .collect()
+}
+
+/// Export/import format for `Database::export_table` / `import_table`.
+pub enum Format {
+    Json,
+    Toml,
+}
+
+/// A generic document value mirroring `ResultDT`, used to round-trip a
+/// table through JSON/TOML. Serialized untagged so each row reads as a
+/// plain object keyed by column name, rather than `{"Str": "..."}`.
+/// `Datetime` and `Str` both wrap a bare `String`, so untagged
+/// deserialization always resolves a JSON/TOML string to whichever of the
+/// two is declared first; that's fine because `import_table` re-parses
+/// every field against the target column's declared type anyway.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum Value {
+    Int(i32),
+    Float(f64),
+    Bool(bool),
+    Datetime(String),
+    Str(String),
+    Array(Vec<Value>),
+    Table(BTreeMap<String, Value>),
+}
+
+fn result_to_value(val: ResultDT) -> Value {
+    match val {
+        ResultDT::Int(v) => Value::Int(v),
+        ResultDT::Float(v) => Value::Float(v),
+        ResultDT::Str(v) => Value::Str(v),
+        ResultDT::Bool(v) => Value::Bool(v),
+        ResultDT::Datetime(v) => Value::Datetime(v.to_rfc3339()),
+        ResultDT::None => Value::Str(String::new()),
+    }
+}
+
+fn value_to_string(val: &Value) -> Result<String, Error> {
+    match val {
+        Value::Int(v) => Ok(v.to_string()),
+        Value::Float(v) => Ok(v.to_string()),
+        Value::Bool(v) => Ok(v.to_string()),
+        Value::Datetime(v) => Ok(v.clone()),
+        Value::Str(v) => Ok(v.clone()),
+        Value::Array(_) | Value::Table(_) => Err(Error::InvalidColumn),
+    }
+}
+
 #[derive(Deserialize, Serialize)]
 pub struct Column {
     pub name: String,
@@ -90,7 +412,9 @@ impl Column {
         let name = name.into();
         let index = match kind {
             DataType::Int => Index::Int(BTreeMap::new()),
+            DataType::Str if is_indexed => Index::Text(BTreeMap::new()),
             DataType::Str => Index::Str(BTreeMap::new()),
+            DataType::Datetime => Index::Datetime(BTreeMap::new()),
             _ => Index::None,
         };
 
@@ -116,6 +440,8 @@ pub enum ColumnData {
     Int(Vec<i32>),
     Float(Vec<f64>),
     Str(Vec<String>),
+    Bool(Vec<bool>),
+    Datetime(Vec<DateTimeValue>),
 }
 
 impl ColumnData {
@@ -124,6 +450,8 @@ impl ColumnData {
             ColumnData::Int(vec) => vec.len(),
             ColumnData::Float(vec) => vec.len(),
             ColumnData::Str(vec) => vec.len(),
+            ColumnData::Bool(vec) => vec.len(),
+            ColumnData::Datetime(vec) => vec.len(),
         }
     }
 
@@ -132,6 +460,8 @@ impl ColumnData {
             ColumnData::Int(vec) => ResultDT::Int(vec[idx]),
             ColumnData::Float(vec) => ResultDT::Float(vec[idx]),
             ColumnData::Str(vec) => ResultDT::Str(vec[idx].clone()),
+            ColumnData::Bool(vec) => ResultDT::Bool(vec[idx]),
+            ColumnData::Datetime(vec) => ResultDT::Datetime(vec[idx]),
         }
     }
 }
@@ -141,6 +471,9 @@ pub struct Table {
     pub name: String,
     pub cols: Vec<Column>,
     pub rows: HashMap<String, ColumnData>,
+    /// Row positions freed by `delete_idx`, kept so existing index entries
+    /// (which store positions, not rows) stay valid; `insert` reuses them.
+    pub tombstones: BTreeSet<usize>,
 }
 
 impl Table {
@@ -153,12 +486,85 @@ impl Table {
                 DataType::Int => ColumnData::Int(Vec::new()),
                 DataType::Float => ColumnData::Float(Vec::new()),
                 DataType::Str => ColumnData::Str(Vec::new()),
+                DataType::Bool => ColumnData::Bool(Vec::new()),
+                DataType::Datetime => ColumnData::Datetime(Vec::new()),
             };
 
             rows.insert(col.name.clone(), col_data);
         }
 
-        Self { name, cols, rows }
+        Self {
+            name,
+            cols,
+            rows,
+            tombstones: BTreeSet::new(),
+        }
+    }
+}
+
+/// A single buffered mutation, as replayed from a WAL file.
+#[derive(Deserialize, Serialize)]
+pub struct Update {
+    pub table: String,
+    pub cols: Vec<String>,
+    pub values: Vec<String>,
+}
+
+/// Number of WAL files a `Database` tolerates before compacting them into
+/// the main file on the next `UpdateBuilder::build`.
+const COMPACT_THRESHOLD: usize = 32;
+
+/// Accumulates mutations for one atomic, durable batch: `build()` fsyncs
+/// them to a WAL file before applying them in memory, so a crash between
+/// the two leaves the WAL to replay on the next `Database::new`.
+pub struct UpdateBuilder<'a> {
+    db: &'a mut Database,
+    pending: Vec<Update>,
+}
+
+impl<'a> UpdateBuilder<'a> {
+    pub fn insert<A, B>(mut self, cols: Vec<A>, values: Vec<A>, table: B) -> Self
+    where
+        A: Into<String>,
+        B: Into<String>,
+    {
+        self.pending.push(Update {
+            table: table.into(),
+            cols: cols.into_iter().map(Into::into).collect(),
+            values: values.into_iter().map(Into::into).collect(),
+        });
+        self
+    }
+
+    pub fn build(self) -> Result<(), Error> {
+        let UpdateBuilder { db, pending } = self;
+        if pending.is_empty() {
+            return Ok(());
+        }
+
+        let seq = db.wal_seq;
+        db.wal_seq += 1;
+        let wal_path = db.wal_path(seq);
+
+        let bytes = bincode::serialize(&pending).map_err(|_| Error::Unknown)?;
+        let mut wal = File::create(&wal_path).map_err(Error::FileError)?;
+        wal.write_all(&bytes)
+            .and_then(|_| wal.sync_data())
+            .map_err(Error::FileError)?;
+
+        for update in &pending {
+            db.insert(
+                update.cols.clone(),
+                update.values.clone(),
+                update.table.clone(),
+            )?;
+        }
+
+        if db.wal_seq >= COMPACT_THRESHOLD {
+            db.compact()?;
+        }
+
+        Ok(())
     }
 }
 
@@ -166,6 +572,7 @@ pub struct Database {
     pub path: PathBuf,
     pub file: File,
     pub tables: Vec<Table>,
+    wal_seq: usize,
 }
 
 impl Database {
@@ -185,12 +592,120 @@ impl Database {
                     .expect("Could not deserialize data! Invalid file format!");
             }
         }
-        let file = File::create(path).unwrap();
-        Self {
+        // Open without truncating: WAL recovery below still needs to read
+        // back whatever `flush`/`compact` last wrote, and a bare
+        // `File::create` would wipe it out before that can happen.
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)
+            .unwrap();
+        let mut db = Self {
             path: path.to_path_buf(),
             file: file,
             tables: tables,
+            wal_seq: 0,
+        };
+
+        let leftover = db.pending_wal_paths();
+        if !leftover.is_empty() {
+            for (seq, wal_path) in &leftover {
+                if let Err(e) = db.ingest_update_file(wal_path) {
+                    eprintln!("Error: {:?}", e);
+                }
+                db.wal_seq = db.wal_seq.max(seq + 1);
+            }
+            if let Err(e) = db.compact() {
+                eprintln!("Error: {:?}", e);
+            }
+        }
+
+        db
+    }
+
+    /// Starts a batched, crash-safe update. Chain `UpdateBuilder::insert`
+    /// calls and finish with `build()` to fsync them to a WAL file before
+    /// they're applied to `self.tables`.
+    pub fn begin_update(&mut self) -> UpdateBuilder<'_> {
+        UpdateBuilder {
+            db: self,
+            pending: Vec::new(),
+        }
+    }
+
+    /// Replays a WAL file's buffered mutations into `self.tables`.
+    pub fn ingest_update_file<P: AsRef<Path>>(&mut self, path: P) -> Result<(), Error> {
+        let mut file = File::open(path).map_err(Error::FileError)?;
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes).map_err(Error::FileError)?;
+        let updates: Vec<Update> =
+            bincode::deserialize(&bytes).map_err(|_| Error::Unknown)?;
+
+        for update in updates {
+            self.insert(update.cols, update.values, update.table)?;
+        }
+
+        Ok(())
+    }
+
+    fn wal_path(&self, seq: usize) -> PathBuf {
+        let mut name = self.path.as_os_str().to_os_string();
+        name.push(format!(".wal.{}", seq));
+        PathBuf::from(name)
+    }
+
+    /// Scratch path `flush` writes to before atomically renaming it over
+    /// the main file, so a crash mid-write never leaves a truncated or
+    /// doubly-concatenated main file behind.
+    fn tmp_path(&self) -> PathBuf {
+        let mut name = self.path.as_os_str().to_os_string();
+        name.push(".tmp");
+        PathBuf::from(name)
+    }
+
+    /// Finds WAL files left behind by an interrupted session, in replay order.
+    fn pending_wal_paths(&self) -> Vec<(usize, PathBuf)> {
+        let dir = self
+            .path
+            .parent()
+            .filter(|p| !p.as_os_str().is_empty())
+            .unwrap_or_else(|| Path::new("."));
+        let prefix = format!(
+            "{}.wal.",
+            self.path.file_name().unwrap_or_default().to_string_lossy()
+        );
+
+        let mut found = Vec::new();
+        if let Ok(entries) = std::fs::read_dir(dir) {
+            for entry in entries.flatten() {
+                let name = entry.file_name();
+                if let Some(seq) = name
+                    .to_string_lossy()
+                    .strip_prefix(&prefix)
+                    .and_then(|s| s.parse::<usize>().ok())
+                {
+                    found.push((seq, entry.path()));
+                }
+            }
+        }
+        found.sort_by_key(|(seq, _)| *seq);
+        found
+    }
+
+    /// Clears out the WAL files already folded into `self.tables`, then
+    /// rewrites the main file. WALs go first: their effects already live
+    /// in `self.tables`, so removing them before (rather than after) the
+    /// flush means a crash here never leaves one around to be replayed a
+    /// second time on top of an already-compacted main file.
+    fn compact(&mut self) -> Result<(), Error> {
+        for (_, wal_path) in self.pending_wal_paths() {
+            let _ = std::fs::remove_file(wal_path);
         }
+        self.flush()?;
+        self.wal_seq = 0;
+        Ok(())
     }
 
     pub fn insert<A, B>(&mut self, cols: Vec<A>, values: Vec<A>, table: B) -> Result<(), Error>
@@ -199,36 +714,87 @@ impl Database {
         B: Into<String>,
     {
         if let Some(table) = self.get_table_mut(table) {
+            // Reuse a tombstoned row position if `delete_idx` freed one,
+            // so index entries (which store positions) stay valid and the
+            // table doesn't grow unbounded under churn.
+            let pos = match table.tombstones.iter().next().copied() {
+                Some(pos) => {
+                    table.tombstones.remove(&pos);
+                    pos
+                }
+                None => table
+                    .cols
+                    .first()
+                    .and_then(|col| table.rows.get(&col.name))
+                    .map(ColumnData::size)
+                    .unwrap_or(0),
+            };
+
             for ((_, col), val) in (0..cols.len()).zip(cols).zip(values) {
                 let val = val.into();
                 let col = col.into();
 
                 if let Some(col) = table.cols.iter_mut().find(|c| c.name == *col) {
                     if let Some(row) = table.rows.get_mut(&col.name) {
-                        let size = row.size();
                         match col.kind {
                             DataType::Int => {
-                                let val = val.parse::<i32>().unwrap();
+                                let val = val.parse::<i32>().map_err(|_| Error::InvalidColumn)?;
                                 if let ColumnData::Int(row) = row {
-                                    row.push(val);
+                                    set_or_push(row, pos, val);
                                 }
 
                                 if let Index::Int(index) = &mut col.index {
-                                    index.insert(val, size);
+                                    let rows = index.entry(val).or_default();
+                                    if let Err(i) = rows.binary_search(&pos) {
+                                        rows.insert(i, pos);
+                                    }
                                 }
                             }
                             DataType::Float => {
-                                let val = val.parse::<f64>().unwrap();
+                                let val = val.parse::<f64>().map_err(|_| Error::InvalidColumn)?;
                                 if let ColumnData::Float(row) = row {
-                                    row.push(val);
+                                    set_or_push(row, pos, val);
                                 }
                             }
                             DataType::Str => {
                                 if let ColumnData::Str(row) = row {
-                                    row.push(val.clone());
+                                    set_or_push(row, pos, val.clone());
+                                }
+                                match &mut col.index {
+                                    Index::Str(index) => {
+                                        let rows = index.entry(val).or_default();
+                                        if let Err(i) = rows.binary_search(&pos) {
+                                            rows.insert(i, pos);
+                                        }
+                                    }
+                                    Index::Text(postings) => {
+                                        for token in tokenize(&val) {
+                                            let rows = postings.entry(token).or_default();
+                                            if let Err(i) = rows.binary_search(&pos) {
+                                                rows.insert(i, pos);
+                                            }
+                                        }
+                                    }
+                                    _ => {}
+                                }
+                            }
+                            DataType::Bool => {
+                                let val = val.parse::<bool>().map_err(|_| Error::InvalidColumn)?;
+                                if let ColumnData::Bool(row) = row {
+                                    set_or_push(row, pos, val);
+                                }
+                            }
+                            DataType::Datetime => {
+                                let val = parse_datetime(&val).map_err(|_| Error::InvalidColumn)?;
+                                if let ColumnData::Datetime(row) = row {
+                                    set_or_push(row, pos, val);
                                 }
-                                if let Index::Str(index) = &mut col.index {
-                                    index.insert(val, size);
+
+                                if let Index::Datetime(index) = &mut col.index {
+                                    let rows = index.entry(val.epoch).or_default();
+                                    if let Err(i) = rows.binary_search(&pos) {
+                                        rows.insert(i, pos);
+                                    }
                                 }
                             }
                         }
@@ -244,13 +810,385 @@ impl Database {
         Ok(())
     }
 
-    // pub fn search<R, A, B>(&self, col: A, val: A, table: B) -> Result<Option<R>, Error>
-    // where
-    //     A: Into<String>,
-    //     B: Into<String>,
-    // {
-    //     Ok(None)
-    // }
+    /// Parses `new` to `col`'s type and overwrites row `idx`, keeping
+    /// `col`'s index (including text postings) consistent with the update.
+    pub fn update_with_idx<A, B>(
+        &mut self,
+        col: A,
+        idx: &usize,
+        new: A,
+        table: B,
+    ) -> Result<(), Error>
+    where
+        A: Into<String>,
+        B: Into<String>,
+    {
+        let new = new.into();
+        let col_name = col.into();
+
+        let table = self.get_table_mut(table).ok_or(Error::InvalidTable)?;
+        if table.tombstones.contains(idx) {
+            return Err(Error::InvalidIndex);
+        }
+
+        if let Some(col) = table.cols.iter_mut().find(|c| c.name == col_name) {
+            if let Some(row) = table.rows.get_mut(&col_name) {
+                if *idx >= row.size() {
+                    return Err(Error::InvalidIndex);
+                }
+
+                match col.kind {
+                    DataType::Int => {
+                        let new = new.parse::<i32>().map_err(|_| Error::InvalidColumn)?;
+                        if let ColumnData::Int(vec) = row {
+                            if let Index::Int(index) = &mut col.index {
+                                if let Some(rows) = index.get_mut(&vec[*idx]) {
+                                    if let Ok(i) = rows.binary_search(idx) {
+                                        rows.remove(i);
+                                    }
+                                    if rows.is_empty() {
+                                        index.remove(&vec[*idx]);
+                                    }
+                                }
+                                let rows = index.entry(new).or_default();
+                                if let Err(i) = rows.binary_search(idx) {
+                                    rows.insert(i, *idx);
+                                }
+                            }
+                            vec[*idx] = new;
+                        }
+                    }
+                    DataType::Float => {
+                        let new = new.parse::<f64>().map_err(|_| Error::InvalidColumn)?;
+                        if let ColumnData::Float(vec) = row {
+                            vec[*idx] = new;
+                        }
+                    }
+                    DataType::Str => {
+                        if let ColumnData::Str(vec) = row {
+                            match &mut col.index {
+                                Index::Str(index) => {
+                                    if let Some(rows) = index.get_mut(&vec[*idx]) {
+                                        if let Ok(i) = rows.binary_search(idx) {
+                                            rows.remove(i);
+                                        }
+                                        if rows.is_empty() {
+                                            index.remove(&vec[*idx]);
+                                        }
+                                    }
+                                    let rows = index.entry(new.clone()).or_default();
+                                    if let Err(i) = rows.binary_search(idx) {
+                                        rows.insert(i, *idx);
+                                    }
+                                }
+                                Index::Text(postings) => {
+                                    for token in tokenize(&vec[*idx]) {
+                                        if let Some(rows) = postings.get_mut(&token) {
+                                            if let Ok(i) = rows.binary_search(idx) {
+                                                rows.remove(i);
+                                            }
+                                            if rows.is_empty() {
+                                                postings.remove(&token);
+                                            }
+                                        }
+                                    }
+                                    for token in tokenize(&new) {
+                                        let rows = postings.entry(token).or_default();
+                                        if let Err(i) = rows.binary_search(idx) {
+                                            rows.insert(i, *idx);
+                                        }
+                                    }
+                                }
+                                _ => {}
+                            }
+                            vec[*idx] = new;
+                        }
+                    }
+                    DataType::Bool => {
+                        let new = new.parse::<bool>().map_err(|_| Error::InvalidColumn)?;
+                        if let ColumnData::Bool(vec) = row {
+                            vec[*idx] = new;
+                        }
+                    }
+                    DataType::Datetime => {
+                        let new = parse_datetime(&new).map_err(|_| Error::InvalidColumn)?;
+                        if let ColumnData::Datetime(vec) = row {
+                            if let Index::Datetime(index) = &mut col.index {
+                                if let Some(rows) = index.get_mut(&vec[*idx].epoch) {
+                                    if let Ok(i) = rows.binary_search(idx) {
+                                        rows.remove(i);
+                                    }
+                                    if rows.is_empty() {
+                                        index.remove(&vec[*idx].epoch);
+                                    }
+                                }
+                                let rows = index.entry(new.epoch).or_default();
+                                if let Err(i) = rows.binary_search(idx) {
+                                    rows.insert(i, *idx);
+                                }
+                            }
+                            vec[*idx] = new;
+                        }
+                    }
+                }
+            }
+        } else {
+            return Err(Error::InvalidColumn);
+        }
+
+        Ok(())
+    }
+
+    /// Tombstones row `idx` instead of physically removing it, so other
+    /// positions stay valid; `insert` reuses the freed slot on its next
+    /// call. Strips `idx` out of every `Column::index` first, so a reused
+    /// slot never still matches the row's old values.
+    pub fn delete_idx<B>(&mut self, idx: &usize, table: B) -> Result<(), Error>
+    where
+        B: Into<String>,
+    {
+        let table = self.get_table_mut(table).ok_or(Error::InvalidTable)?;
+
+        let size = table
+            .cols
+            .first()
+            .and_then(|col| table.rows.get(&col.name))
+            .map(ColumnData::size)
+            .unwrap_or(0);
+
+        if *idx >= size {
+            return Err(Error::InvalidIndex);
+        }
+
+        for col in &mut table.cols {
+            let Some(row) = table.rows.get(&col.name) else {
+                continue;
+            };
+
+            match (&mut col.index, row) {
+                (Index::Int(index), ColumnData::Int(vec)) => {
+                    if let Some(rows) = index.get_mut(&vec[*idx]) {
+                        if let Ok(i) = rows.binary_search(idx) {
+                            rows.remove(i);
+                        }
+                        if rows.is_empty() {
+                            index.remove(&vec[*idx]);
+                        }
+                    }
+                }
+                (Index::Str(index), ColumnData::Str(vec)) => {
+                    if let Some(rows) = index.get_mut(&vec[*idx]) {
+                        if let Ok(i) = rows.binary_search(idx) {
+                            rows.remove(i);
+                        }
+                        if rows.is_empty() {
+                            index.remove(&vec[*idx]);
+                        }
+                    }
+                }
+                (Index::Text(postings), ColumnData::Str(vec)) => {
+                    for token in tokenize(&vec[*idx]) {
+                        if let Some(rows) = postings.get_mut(&token) {
+                            if let Ok(i) = rows.binary_search(idx) {
+                                rows.remove(i);
+                            }
+                            if rows.is_empty() {
+                                postings.remove(&token);
+                            }
+                        }
+                    }
+                }
+                (Index::Datetime(index), ColumnData::Datetime(vec)) => {
+                    if let Some(rows) = index.get_mut(&vec[*idx].epoch) {
+                        if let Ok(i) = rows.binary_search(idx) {
+                            rows.remove(i);
+                        }
+                        if rows.is_empty() {
+                            index.remove(&vec[*idx].epoch);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        table.tombstones.insert(*idx);
+        Ok(())
+    }
+
+    /// Full-text search over an indexed `Str` column: tokenizes `query`,
+    /// fuzzy-matches each token (edit distance <= 2) against the column's
+    /// dictionary and intersects the resulting posting lists.
+    pub fn search<A, B>(&self, col: A, query: A, table: B) -> Result<Vec<usize>, Error>
+    where
+        A: Into<String>,
+        B: Into<String>,
+    {
+        let col = col.into();
+        let query = query.into();
+
+        let table = self.get_table(table).ok_or(Error::InvalidTable)?;
+        let col = table
+            .cols
+            .iter()
+            .find(|c| c.name == col)
+            .ok_or(Error::InvalidColumn)?;
+
+        let postings = match &col.index {
+            Index::Text(postings) => postings,
+            _ => return Err(Error::InvalidColumn),
+        };
+
+        let mut rows: Option<Vec<usize>> = None;
+        for token in tokenize(&query) {
+            let max_dist = if token.len() <= 3 { 1 } else { 2 };
+
+            let mut matched = Vec::new();
+            for term in fuzzy_terms(postings, &token, max_dist) {
+                matched.extend(postings[term].iter().copied());
+            }
+            matched.sort_unstable();
+            matched.dedup();
+
+            rows = Some(match rows {
+                Some(acc) => acc
+                    .into_iter()
+                    .filter(|r| matched.binary_search(r).is_ok())
+                    .collect(),
+                None => matched,
+            });
+        }
+
+        Ok(rows
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|r| !table.tombstones.contains(r))
+            .collect())
+    }
+
+    /// WHERE-clause query: evaluates `predicate` against `table`, using an
+    /// indexed `BTreeMap::range` when the predicate's column is indexed and
+    /// falling back to a linear scan otherwise, then projects `cols`.
+    pub fn select<A, B>(
+        &self,
+        cols: Vec<A>,
+        predicate: Predicate,
+        table: B,
+    ) -> Result<Vec<Vec<ResultDT>>, Error>
+    where
+        A: Into<String>,
+        B: Into<String>,
+    {
+        let table = self.get_table(table).ok_or(Error::InvalidTable)?;
+        let pred_col = table
+            .cols
+            .iter()
+            .find(|c| c.name == predicate.col())
+            .ok_or(Error::InvalidColumn)?;
+
+        let positions = match &pred_col.index {
+            Index::Int(index) => int_range_positions(index, &predicate)?,
+            Index::Str(index) => str_range_positions(index, &predicate),
+            Index::Datetime(index) => datetime_range_positions(index, &predicate)?,
+            _ => scan_positions(table, pred_col, &predicate)?,
+        };
+        let positions: Vec<usize> = positions
+            .into_iter()
+            .filter(|pos| !table.tombstones.contains(pos))
+            .collect();
+
+        let cols: Vec<String> = cols.into_iter().map(Into::into).collect();
+        let mut out = Vec::with_capacity(positions.len());
+        for pos in positions {
+            let mut row = Vec::with_capacity(cols.len());
+            for col in &cols {
+                let data = table.rows.get(col).ok_or(Error::InvalidColumn)?;
+                row.push(data.get_from_idx(pos));
+            }
+            out.push(row);
+        }
+
+        Ok(out)
+    }
+
+    /// Serializes every row of `name` to a human-readable JSON/TOML
+    /// document: `{ "<name>": [ { col: val, ... }, ... ] }`.
+    pub fn export_table<S: Into<String>>(&self, name: S, format: Format) -> Result<String, Error> {
+        let name = name.into();
+        let table = self.get_table(&name).ok_or(Error::InvalidTable)?;
+
+        let size = table
+            .cols
+            .first()
+            .and_then(|col| table.rows.get(&col.name))
+            .map(ColumnData::size)
+            .unwrap_or(0);
+
+        let mut rows = Vec::with_capacity(size);
+        for idx in 0..size {
+            if table.tombstones.contains(&idx) {
+                continue;
+            }
+
+            let mut row = BTreeMap::new();
+            for col in &table.cols {
+                let data = table.rows.get(&col.name).ok_or(Error::InvalidColumn)?;
+                row.insert(col.name.clone(), result_to_value(data.get_from_idx(idx)));
+            }
+            rows.push(Value::Table(row));
+        }
+
+        let mut doc = BTreeMap::new();
+        doc.insert(name, Value::Array(rows));
+
+        match format {
+            Format::Json => serde_json::to_string_pretty(&doc).map_err(|_| Error::Unknown),
+            Format::Toml => toml::to_string_pretty(&doc).map_err(|_| Error::Unknown),
+        }
+    }
+
+    /// Parses a document produced by `export_table` (or hand-written
+    /// config-style TOML/JSON) and bulk-inserts its rows into the
+    /// matching, already-created tables.
+    pub fn import_table<S: Into<String>>(&mut self, text: S, format: Format) -> Result<(), Error> {
+        let text = text.into();
+        let doc: BTreeMap<String, Value> = match format {
+            Format::Json => serde_json::from_str(&text).map_err(|_| Error::Unknown)?,
+            Format::Toml => toml::from_str(&text).map_err(|_| Error::Unknown)?,
+        };
+
+        for (table, value) in doc {
+            let rows = match value {
+                Value::Array(rows) => rows,
+                _ => return Err(Error::InvalidTable),
+            };
+
+            let cols: Vec<String> = self
+                .get_table(&table)
+                .ok_or(Error::InvalidTable)?
+                .cols
+                .iter()
+                .map(|col| col.name.clone())
+                .collect();
+
+            for row in rows {
+                let fields = match row {
+                    Value::Table(fields) => fields,
+                    _ => return Err(Error::InvalidTable),
+                };
+
+                let mut values = Vec::with_capacity(cols.len());
+                for col in &cols {
+                    let val = fields.get(col).ok_or(Error::InvalidColumn)?;
+                    values.push(value_to_string(val)?);
+                }
+
+                self.insert(cols.clone(), values, table.clone())?;
+            }
+        }
+
+        Ok(())
+    }
 
     pub fn search_idx<A, B>(
         &self,
@@ -264,6 +1202,10 @@ impl Database {
     {
         let mut data = Vec::new();
         if let Some(table) = self.get_table(table) {
+            if table.tombstones.contains(idx) {
+                return Err(Error::NotFound);
+            }
+
             for col in s_col {
                 let col = col.into();
                 if let Some(row) = table.rows.get(&col) {
@@ -277,39 +1219,6 @@ impl Database {
         Ok(data)
     }
 
-    // pub fn update<A, B>(&mut self, idx: &usize, table: B) -> Result<(), Error>
-    // where
-    //     A: Into<String>,
-    //     B: Into<String>,
-    // {
-    //     if let Some(table) = self.get_table_mut(table) {}
-
-    //     Err(Error::Unknown)
-    // }
-
-    // pub fn update_with_idx<A, B>(
-    //     &mut self,
-    //     col: A,
-    //     idx: &usize,
-    //     new: A,
-    //     table: B,
-    // ) -> Result<(), Error>
-    // where
-    //     A: Into<String>,
-    //     B: Into<String>,
-    // {
-    //     if let Some(table) = self.get_table_mut(table) {
-    //         let col = col.into();
-    //         if let Some(col) = table.cols.get(&col) {
-    //             if let Some(row) = table.rows.get_mut(&col) {
-    //                 row.get_from_idx_mut(*idx) = new;
-    //             }
-    //         }
-    //     }
-
-    //     Err(Error::Unknown)
-    // }
-
     pub fn insert_table(&mut self, table: Table) -> Result<(), Error> {
         if let Some(_) = self.get_table(&table.name) {
             return Err(Error::TableAlreadyExists);
@@ -330,24 +1239,34 @@ impl Database {
         self.tables.iter_mut().find(|t| t.name == table)
     }
 
+    /// Writes the full snapshot to a scratch file and renames it over the
+    /// main file, rather than rewriting `self.file` in place: an in-place
+    /// `write_all` neither truncates nor seeks back to the start, so a
+    /// second call in the same session would append a second snapshot
+    /// after the first instead of replacing it, and a crash mid-write
+    /// would leave a half-written file behind either way.
     pub fn flush(&mut self) -> Result<(), Error> {
-        let data = &self.tables;
+        let bytes: Vec<u8> = bincode::serialize(&self.tables).unwrap();
 
-        let bytes: Vec<u8> = bincode::serialize(&data).unwrap();
-        match self
-            .file
-            .write_all(&bytes)
-            .and_then(|_| self.file.sync_data())
-        {
-            Ok(()) => Ok(()),
-            Err(e) => Err(Error::FileError(e)),
-        }
+        let tmp_path = self.tmp_path();
+        let mut tmp = File::create(&tmp_path).map_err(Error::FileError)?;
+        tmp.write_all(&bytes)
+            .and_then(|_| tmp.sync_data())
+            .map_err(Error::FileError)?;
+        std::fs::rename(&tmp_path, &self.path).map_err(Error::FileError)?;
+
+        self.file = File::open(&self.path).map_err(Error::FileError)?;
+        Ok(())
     }
 }
 
 impl Drop for Database {
     fn drop(&mut self) {
-        match self.flush() {
+        // `compact`, not `flush`: a plain flush rewrites the main file but
+        // leaves WAL files on disk, so the next `Database::new` would
+        // replay already-durable batches on top of the current main file
+        // and duplicate rows. Compacting also deletes the WALs it folds in.
+        match self.compact() {
             Ok(()) => {}
             Err(e) => eprintln!("Error: {:?}", e),
         }
@@ -376,6 +1295,13 @@ mod tests {
         let data = vec!["Tommy", "16"];
 
         assert_eq!(db.insert(columns, data, table).is_err(), false);
+
+        // malformed Int input yields an error instead of panicking
+        assert_eq!(
+            db.insert(vec!["name", "age"], vec!["Ada", "not-a-number"], table)
+                .is_err(),
+            true
+        );
     }
 
     #[test]
@@ -426,4 +1352,446 @@ mod tests {
         assert_eq!(db.insert_table(people1).is_err(), false);
         assert_eq!(db.insert_table(people2).is_err(), true);
     }
+
+    #[test]
+    fn test_search() {
+        let mut db = Database::new("./test4.db");
+        let notes = Table::new(
+            "notes",
+            vec![
+                Column::new("body", DataType::Str, true),
+                Column::new("id", DataType::Int, false),
+            ],
+        );
+
+        assert_eq!(db.insert_table(notes).is_err(), false);
+
+        let table = "notes";
+        db.insert(
+            vec!["body", "id"],
+            vec!["The quick brown fox", "0"],
+            table,
+        )
+        .unwrap();
+        db.insert(vec!["body", "id"], vec!["A lazy dog", "1"], table)
+            .unwrap();
+
+        assert_eq!(db.search("body", "quick", table).unwrap(), vec![0]);
+        // one edit away from "quick" should still match via the fuzzy pass
+        assert_eq!(db.search("body", "quack", table).unwrap(), vec![0]);
+        assert_eq!(db.search("body", "lazy", table).unwrap(), vec![1]);
+    }
+
+    #[test]
+    fn test_begin_update() {
+        let mut db = Database::new("./test5.db");
+        let people = Table::new(
+            "people",
+            vec![
+                Column::new("name", DataType::Str, true),
+                Column::new("age", DataType::Int, false),
+            ],
+        );
+
+        assert_eq!(db.insert_table(people).is_err(), false);
+
+        db.begin_update()
+            .insert(vec!["name", "age"], vec!["Tommy", "16"], "people")
+            .insert(vec!["name", "age"], vec!["Ada", "28"], "people")
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            db.search_idx(vec!["name", "age"], &0, "people").unwrap(),
+            vec![ResultDT::Str("Tommy".to_string()), ResultDT::Int(16)]
+        );
+        assert_eq!(
+            db.search_idx(vec!["name", "age"], &1, "people").unwrap(),
+            vec![ResultDT::Str("Ada".to_string()), ResultDT::Int(28)]
+        );
+    }
+
+    #[test]
+    fn test_wal_compacted_on_clean_shutdown() {
+        let path = "./test13.db";
+
+        {
+            let mut db = Database::new(path);
+            let people = Table::new(
+                "people",
+                vec![
+                    Column::new("name", DataType::Str, false),
+                    Column::new("age", DataType::Int, false),
+                ],
+            );
+            db.insert_table(people).unwrap();
+            db.begin_update()
+                .insert(vec!["name", "age"], vec!["Tommy", "16"], "people")
+                .build()
+                .unwrap();
+        } // `db` dropped here; a clean shutdown must leave no WAL to replay
+
+        let db = Database::new(path);
+        let size = db
+            .get_table("people")
+            .unwrap()
+            .rows
+            .get("name")
+            .unwrap()
+            .size();
+        assert_eq!(size, 1);
+    }
+
+    #[test]
+    fn test_repeated_flush_does_not_concatenate_the_file() {
+        let path = "./test14.db";
+
+        {
+            let mut db = Database::new(path);
+            db.insert_table(Table::new(
+                "people",
+                vec![Column::new("name", DataType::Str, false)],
+            ))
+            .unwrap();
+            db.insert(vec!["name"], vec!["Tommy"], "people").unwrap();
+
+            // two flushes in one session, as happen on the ordinary
+            // "recover a WAL, then shut down cleanly" lifecycle (one from
+            // `compact` during `Database::new`'s WAL recovery, one from
+            // `Drop`); the second must replace the file, not append to it.
+            db.flush().unwrap();
+            db.flush().unwrap();
+        }
+
+        let db = Database::new(path);
+        let size = db
+            .get_table("people")
+            .unwrap()
+            .rows
+            .get("name")
+            .unwrap()
+            .size();
+        assert_eq!(size, 1);
+    }
+
+    #[test]
+    fn test_select() {
+        let mut db = Database::new("./test6.db");
+        let people = Table::new(
+            "people",
+            vec![
+                Column::new("name", DataType::Str, false),
+                Column::new("age", DataType::Int, true),
+            ],
+        );
+
+        assert_eq!(db.insert_table(people).is_err(), false);
+
+        let table = "people";
+        db.insert(vec!["name", "age"], vec!["Tommy", "16"], table)
+            .unwrap();
+        db.insert(vec!["name", "age"], vec!["Ada", "28"], table)
+            .unwrap();
+        db.insert(vec!["name", "age"], vec!["Linus", "54"], table)
+            .unwrap();
+
+        // indexed range predicate, via BTreeMap::range on Index::Int
+        assert_eq!(
+            db.select(vec!["name"], Predicate::Ge("age".to_string(), "20".to_string()), table)
+                .unwrap(),
+            vec![
+                vec![ResultDT::Str("Ada".to_string())],
+                vec![ResultDT::Str("Linus".to_string())]
+            ]
+        );
+
+        // un-indexed column predicate, via the linear scan fallback
+        assert_eq!(
+            db.select(
+                vec!["age"],
+                Predicate::Eq("name".to_string(), "Tommy".to_string()),
+                table
+            )
+            .unwrap(),
+            vec![vec![ResultDT::Int(16)]]
+        );
+
+        // duplicate-valued indexed column: every matching row comes back,
+        // not just the first one to claim that key
+        db.insert(vec!["name", "age"], vec!["Bea", "28"], table)
+            .unwrap();
+        assert_eq!(
+            db.select(vec!["name"], Predicate::Eq("age".to_string(), "28".to_string()), table)
+                .unwrap(),
+            vec![
+                vec![ResultDT::Str("Ada".to_string())],
+                vec![ResultDT::Str("Bea".to_string())]
+            ]
+        );
+    }
+
+    #[test]
+    fn test_bool_and_datetime_columns() {
+        let mut db = Database::new("./test7.db");
+        let events = Table::new(
+            "events",
+            vec![
+                Column::new("name", DataType::Str, false),
+                Column::new("active", DataType::Bool, false),
+                Column::new("at", DataType::Datetime, true),
+            ],
+        );
+
+        assert_eq!(db.insert_table(events).is_err(), false);
+
+        let table = "events";
+        db.insert(
+            vec!["name", "active", "at"],
+            vec!["launch", "true", "2020-01-01T00:00:00Z"],
+            table,
+        )
+        .unwrap();
+        db.insert(
+            vec!["name", "active", "at"],
+            vec!["shutdown", "false", "2021-06-15T12:30:00Z"],
+            table,
+        )
+        .unwrap();
+
+        assert_eq!(
+            db.search_idx(vec!["name", "active"], &0, table).unwrap(),
+            vec![
+                ResultDT::Str("launch".to_string()),
+                ResultDT::Bool(true)
+            ]
+        );
+
+        // indexed range predicate on Index::Datetime
+        assert_eq!(
+            db.select(
+                vec!["name"],
+                Predicate::Ge("at".to_string(), "2021-01-01T00:00:00Z".to_string()),
+                table
+            )
+            .unwrap(),
+            vec![vec![ResultDT::Str("shutdown".to_string())]]
+        );
+
+        // malformed input yields an error instead of panicking
+        assert_eq!(
+            db.insert(
+                vec!["name", "active", "at"],
+                vec!["bad", "not-a-bool", "2020-01-01T00:00:00Z"],
+                table,
+            )
+            .is_err(),
+            true
+        );
+        assert_eq!(
+            db.insert(
+                vec!["name", "active", "at"],
+                vec!["bad", "true", "not-a-datetime"],
+                table,
+            )
+            .is_err(),
+            true
+        );
+    }
+
+    #[test]
+    fn test_export_import_table() {
+        let mut src = Database::new("./test8.db");
+        let people = Table::new(
+            "people",
+            vec![
+                Column::new("name", DataType::Str, false),
+                Column::new("age", DataType::Int, false),
+            ],
+        );
+
+        assert_eq!(src.insert_table(people).is_err(), false);
+
+        let table = "people";
+        src.insert(vec!["name", "age"], vec!["Tommy", "16"], table)
+            .unwrap();
+        src.insert(vec!["name", "age"], vec!["Ada", "28"], table)
+            .unwrap();
+
+        let json = src.export_table(table, Format::Json).unwrap();
+
+        let mut dst = Database::new("./test9.db");
+        let people = Table::new(
+            "people",
+            vec![
+                Column::new("name", DataType::Str, false),
+                Column::new("age", DataType::Int, false),
+            ],
+        );
+        assert_eq!(dst.insert_table(people).is_err(), false);
+
+        dst.import_table(json, Format::Json).unwrap();
+
+        assert_eq!(
+            dst.search_idx(vec!["name", "age"], &0, table).unwrap(),
+            vec![ResultDT::Str("Tommy".to_string()), ResultDT::Int(16)]
+        );
+        assert_eq!(
+            dst.search_idx(vec!["name", "age"], &1, table).unwrap(),
+            vec![ResultDT::Str("Ada".to_string()), ResultDT::Int(28)]
+        );
+    }
+
+    #[test]
+    fn test_export_table_skips_tombstoned_rows() {
+        let mut db = Database::new("./test15.db");
+        let people = Table::new(
+            "people",
+            vec![
+                Column::new("name", DataType::Str, false),
+                Column::new("age", DataType::Int, false),
+            ],
+        );
+
+        assert_eq!(db.insert_table(people).is_err(), false);
+
+        let table = "people";
+        db.insert(vec!["name", "age"], vec!["Tommy", "16"], table)
+            .unwrap();
+        db.insert(vec!["name", "age"], vec!["Ada", "28"], table)
+            .unwrap();
+        db.delete_idx(&0, table).unwrap();
+
+        let json = db.export_table(table, Format::Json).unwrap();
+        assert_eq!(
+            json,
+            r#"{
+  "people": [
+    {
+      "age": 28,
+      "name": "Ada"
+    }
+  ]
+}"#
+        );
+    }
+
+    #[test]
+    fn test_update_with_idx() {
+        let mut db = Database::new("./test10.db");
+        let people = Table::new(
+            "people",
+            vec![
+                Column::new("name", DataType::Str, true),
+                Column::new("age", DataType::Int, true),
+            ],
+        );
+
+        assert_eq!(db.insert_table(people).is_err(), false);
+
+        let table = "people";
+        db.insert(vec!["name", "age"], vec!["Tommy", "16"], table)
+            .unwrap();
+
+        db.update_with_idx("age", &0, "17", table).unwrap();
+        assert_eq!(
+            db.search_idx(vec!["age"], &0, table).unwrap(),
+            vec![ResultDT::Int(17)]
+        );
+
+        db.update_with_idx("name", &0, "Zephyr", table).unwrap();
+        assert_eq!(
+            db.search_idx(vec!["name"], &0, table).unwrap(),
+            vec![ResultDT::Str("Zephyr".to_string())]
+        );
+        // the old text-index entry for "tommy" must be gone, "zephyr" present
+        assert_eq!(
+            db.search("name", "tommy", table).unwrap(),
+            Vec::<usize>::new()
+        );
+        assert_eq!(db.search("name", "zephyr", table).unwrap(), vec![0]);
+
+        assert_eq!(
+            db.update_with_idx("age", &0, "nope", table).is_err(),
+            true
+        );
+        assert_eq!(db.update_with_idx("age", &5, "1", table).is_err(), true);
+    }
+
+    #[test]
+    fn test_delete_idx_and_reuse() {
+        let mut db = Database::new("./test11.db");
+        let people = Table::new(
+            "people",
+            vec![
+                Column::new("name", DataType::Str, false),
+                Column::new("age", DataType::Int, false),
+            ],
+        );
+
+        assert_eq!(db.insert_table(people).is_err(), false);
+
+        let table = "people";
+        db.insert(vec!["name", "age"], vec!["Tommy", "16"], table)
+            .unwrap();
+        db.insert(vec!["name", "age"], vec!["Ada", "28"], table)
+            .unwrap();
+
+        db.delete_idx(&0, table).unwrap();
+        assert_eq!(db.search_idx(vec!["name"], &0, table).is_err(), true);
+        assert_eq!(
+            db.search_idx(vec!["name"], &1, table).unwrap(),
+            vec![ResultDT::Str("Ada".to_string())]
+        );
+
+        // the next insert reuses the tombstoned slot rather than growing
+        db.insert(vec!["name", "age"], vec!["Linus", "54"], table)
+            .unwrap();
+        assert_eq!(
+            db.search_idx(vec!["name", "age"], &0, table).unwrap(),
+            vec![ResultDT::Str("Linus".to_string()), ResultDT::Int(54)]
+        );
+    }
+
+    #[test]
+    fn test_delete_idx_clears_indexed_columns() {
+        let mut db = Database::new("./test12.db");
+        let people = Table::new(
+            "people",
+            vec![
+                Column::new("name", DataType::Str, true),
+                Column::new("age", DataType::Int, true),
+            ],
+        );
+
+        assert_eq!(db.insert_table(people).is_err(), false);
+
+        let table = "people";
+        db.insert(vec!["name", "age"], vec!["Tommy", "16"], table)
+            .unwrap();
+
+        db.delete_idx(&0, table).unwrap();
+        // reuses slot 0, must not leave "tommy"/16 reachable through either index
+        db.insert(vec!["name", "age"], vec!["Linus", "54"], table)
+            .unwrap();
+
+        assert_eq!(
+            db.select(
+                vec!["name"],
+                Predicate::Eq("age".to_string(), "16".to_string()),
+                table
+            )
+            .unwrap(),
+            Vec::<Vec<ResultDT>>::new()
+        );
+        assert_eq!(db.search("name", "tommy", table).unwrap(), Vec::<usize>::new());
+        assert_eq!(
+            db.select(
+                vec!["name"],
+                Predicate::Eq("age".to_string(), "54".to_string()),
+                table
+            )
+            .unwrap(),
+            vec![vec![ResultDT::Str("Linus".to_string())]]
+        );
+    }
 }